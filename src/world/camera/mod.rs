@@ -1,37 +1,224 @@
+use std::f32::consts::FRAC_PI_4;
 use bevy::app::{Startup, Update};
 use bevy::input::mouse::MouseWheel;
 use bevy::math::Vec3;
-use bevy::prelude::{ButtonInput, Camera, Camera3d, Commands, Component, EulerRot, EventReader, FixedUpdate, GlobalTransform, KeyCode, MouseButton, Quat, Query, Ray3d, Res, ResMut, Resource, Time, Transform, Vec2, Window};
+use bevy::prelude::{ButtonInput, Camera, Camera3d, Commands, Component, Entity, EulerRot, EventReader, FixedUpdate, GlobalTransform, KeyCode, MouseButton, Projection, Quat, Query, Ray3d, Res, ResMut, Resource, Time, Transform, Vec2, Window};
+use bevy::window::{CursorIcon, SystemCursorIcon};
+use crate::world::map::terrain::{raycast_heightmap, TerrainHeightmap};
 
 const MAP_MIN_X: f32 = -256.0;
 const MAP_MAX_X: f32 = 8192.0 + 256.0;
 const MAP_MIN_Z: f32 = -256.0;
 const MAP_MAX_Z: f32 = 4096.0 + 256.0;
 
+const CAMERA_FRICTION: f32 = 6.0;
+const VELOCITY_EPSILON_SQ: f32 = 0.0001;
+
+const BOOKMARK_SLOTS: usize = 4;
+const BOOKMARK_EASE_EPSILON_SQ: f32 = 0.01;
+const BOOKMARK_YAW_EPSILON: f32 = 0.001;
+
 #[derive(Component)]
 struct CameraController {
-    zoom: CameraZoom
+    zoom: CameraZoom,
+    /// World-space units per second. Driven directly to full speed by
+    /// keyboard/drag input each frame they're active, then decayed by
+    /// `friction` once input stops, so panning glides to a stop instead of
+    /// halting dead.
+    velocity: Vec3,
+    friction: f32,
+    /// Radians. Accumulated by `camera_rotation` and read back by
+    /// `zoom_handler` when it rebuilds the view rotation, so pitch changes
+    /// from zooming don't clobber a manual yaw.
+    yaw: f32,
+    /// Set by `handle_bookmark_input` on recall; `apply_bookmark_recall`
+    /// eases translation and yaw toward it each tick and clears it on
+    /// arrival (or if WASD/drag input takes over).
+    bookmark_target: Option<CameraBookmarkTarget>,
+    /// Whether `camera_movement` drove `velocity` this tick (WASD or
+    /// edge-pan), so `integrate_camera_velocity` knows not to apply
+    /// friction on top of a value that was just set.
+    driven_this_tick: bool,
+}
+
+#[derive(Clone, Copy)]
+struct CameraBookmarkTarget {
+    translation: Vec3,
+    yaw: f32,
+}
+
+/// `Height` moves the camera up/down and derives pitch from height (the
+/// original behavior); `Fov` keeps the camera in place and zooms the lens
+/// instead, for inspecting units without flying down to them; `Both` does
+/// both at once.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ZoomMode {
+    Height,
+    Fov,
+    Both,
 }
 
 struct CameraZoom {
-    speed: f32,
+    mode: ZoomMode,
     target_height: f32,
     current_height: f32,
-    smooth_factor: f32,
+    target_fov: f32,
+    current_fov: f32,
+}
+
+/// User-remappable camera bindings and feel, with a `Default` matching the
+/// values that used to be hardcoded. Read by `camera_movement`,
+/// `camera_drag_movement`, and `zoom_handler` instead of literals, so keys
+/// and feel can be tuned (and eventually loaded from a config file) without
+/// recompiling.
+#[derive(Resource)]
+pub struct CameraControls {
+    pub pan_forward: KeyCode,
+    pub pan_backward: KeyCode,
+    pub pan_left: KeyCode,
+    pub pan_right: KeyCode,
+    pub drag_button: MouseButton,
+    /// Flips which scroll direction zooms in vs. out.
+    pub invert_zoom: bool,
+    pub movement_speed: f32,
+    pub zoom_speed: f32,
+    pub smooth_factor: f32,
+    pub rotate_left: KeyCode,
+    pub rotate_right: KeyCode,
+    /// Radians per second while a rotate key is held.
+    pub rotation_speed: f32,
+    /// Held together with a slot key to store a bookmark instead of
+    /// recalling it.
+    pub bookmark_store_modifier: KeyCode,
+    pub bookmark_slot_keys: [KeyCode; BOOKMARK_SLOTS],
+    pub bookmark_cycle_key: KeyCode,
+    pub fov_zoom_speed: f32,
+    pub min_fov: f32,
+    pub max_fov: f32,
+    /// Pixels from a window border within which the cursor starts
+    /// edge-panning the camera.
+    pub edge_pan_margin: f32,
+    /// World-space units per second at the very edge of the window; ramps
+    /// down to 0 at `edge_pan_margin` pixels from the border.
+    pub edge_pan_speed: f32,
+    /// Shown on the window while `drag_button` is held and dragging the
+    /// camera. Kept as data (rather than hardcoded in
+    /// `camera_drag_movement`) so swapping in a `CursorIcon::Custom` image
+    /// later is a config change, not a code change.
+    pub grabbing_cursor_icon: CursorIcon,
+    /// Restored when the drag button is released, unless the window already
+    /// had a different icon when the drag started (see
+    /// `CameraDragState::previous_cursor_icon`).
+    pub grab_cursor_icon: CursorIcon,
 }
 
+impl Default for CameraControls {
+    fn default() -> Self {
+        Self {
+            pan_forward: KeyCode::KeyW,
+            pan_backward: KeyCode::KeyS,
+            pan_left: KeyCode::KeyA,
+            pan_right: KeyCode::KeyD,
+            drag_button: MouseButton::Right,
+            invert_zoom: false,
+            movement_speed: 440.0,
+            zoom_speed: 1200.0,
+            smooth_factor: 0.1,
+            rotate_left: KeyCode::KeyQ,
+            rotate_right: KeyCode::KeyE,
+            rotation_speed: 1.5,
+            bookmark_store_modifier: KeyCode::ControlLeft,
+            bookmark_slot_keys: [KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3, KeyCode::Digit4],
+            bookmark_cycle_key: KeyCode::KeyC,
+            fov_zoom_speed: 0.6,
+            min_fov: 0.3,
+            max_fov: 1.3,
+            edge_pan_margin: 24.0,
+            edge_pan_speed: 440.0,
+            grabbing_cursor_icon: CursorIcon::System(SystemCursorIcon::Grabbing),
+            grab_cursor_icon: CursorIcon::System(SystemCursorIcon::Default),
+        }
+    }
+}
+
+
 #[derive(Resource, Default)]
 struct CameraDragState {
     is_dragging: bool,
-    drag_start_world_position: Option<Vec3>,
+    last_world_position: Option<Vec3>,
+    /// The window's cursor icon at the moment the drag started, so it can be
+    /// restored exactly on release instead of always falling back to
+    /// `CameraControls::grab_cursor_icon`.
+    previous_cursor_icon: Option<CursorIcon>,
+}
+
+#[derive(Clone, Copy)]
+struct CameraBookmark {
+    translation: Vec3,
+    target_height: f32,
+    yaw: f32,
+}
+
+/// Up to `BOOKMARK_SLOTS` saved viewpoints, stored/recalled by
+/// `handle_bookmark_input`. `cycle_index` is the slot last recalled, by a
+/// slot key or the cycle key, so repeated cycle presses walk through every
+/// stored slot in order from there.
+#[derive(Resource, Default)]
+struct CameraBookmarks {
+    slots: [Option<CameraBookmark>; BOOKMARK_SLOTS],
+    cycle_index: usize,
+}
+
+/// The terrain point under the cursor, updated every frame by
+/// `update_cursor_terrain_position`. `None` when the cursor isn't over the
+/// window or isn't over any terrain. Gameplay systems (unit selection,
+/// placement) read this instead of raycasting the heightmap themselves.
+#[derive(Resource, Default)]
+pub struct CursorTerrainPosition(pub Option<Vec3>);
+
+/// Gates edge-pan scrolling. UI code should set this to `false` while the
+/// cursor is over a UI panel near a window border, so hovering a sidebar
+/// doesn't also drag the camera.
+#[derive(Resource)]
+pub struct EdgePanEnabled(pub bool);
+
+impl Default for EdgePanEnabled {
+    fn default() -> Self {
+        Self(true)
+    }
 }
 
 pub fn build(app: &mut bevy::prelude::App) {
     app.add_systems(Startup, spawn_camera);
     app.add_systems(Update, camera_drag_movement);
-    app.add_systems(FixedUpdate, zoom_handler);
-    app.add_systems(FixedUpdate, camera_movement);
+    app.add_systems(Update, update_cursor_terrain_position);
+    app.add_systems(Update, handle_bookmark_input);
+    app.add_systems(FixedUpdate, (apply_bookmark_recall, camera_rotation, zoom_handler).chain());
+    app.add_systems(FixedUpdate, (camera_movement, integrate_camera_velocity).chain());
     app.init_resource::<CameraDragState>();
+    app.init_resource::<CursorTerrainPosition>();
+    app.init_resource::<CameraControls>();
+    app.init_resource::<CameraBookmarks>();
+    app.init_resource::<EdgePanEnabled>();
+}
+
+fn update_cursor_terrain_position(
+    window: Query<&Window>,
+    heightmap: Option<Res<TerrainHeightmap>>,
+    mut cursor_terrain_position: ResMut<CursorTerrainPosition>,
+    query: Query<(&GlobalTransform, &Camera)>,
+) {
+    let Some(heightmap) = heightmap else {
+        cursor_terrain_position.0 = None;
+        return;
+    };
+
+    let window = window.single();
+    let (global_transform, camera) = query.single();
+
+    cursor_terrain_position.0 = window.cursor_position()
+        .and_then(|cursor_position| camera.viewport_to_world(global_transform, cursor_position).ok())
+        .and_then(|ray| raycast_heightmap(ray.origin, *ray.direction, &heightmap.0));
 }
 
 fn spawn_camera(mut commands: Commands) {
@@ -42,50 +229,202 @@ fn spawn_camera(mut commands: Commands) {
         Transform::from_xyz(1100.0, initial_height, 720.0),
         CameraController {
             zoom: CameraZoom {
-                speed: 1200.0,
+                mode: ZoomMode::Height,
                 target_height: initial_height,
                 current_height: initial_height,
-                smooth_factor: 0.1,
-            }
+                target_fov: FRAC_PI_4,
+                current_fov: FRAC_PI_4,
+            },
+            velocity: Vec3::ZERO,
+            friction: CAMERA_FRICTION,
+            yaw: 0.0,
+            bookmark_target: None,
+            driven_this_tick: false,
         }
     ));
 }
 
-fn camera_drag_movement(
+/// Ctrl+slot stores the current view; plain slot recalls it; the cycle key
+/// recalls whichever stored slot comes after the last one recalled.
+fn handle_bookmark_input(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    controls: Res<CameraControls>,
+    mut bookmarks: ResMut<CameraBookmarks>,
+    mut query: Query<(&mut CameraController, &Transform)>,
+) {
+    let Ok((mut controller, transform)) = query.get_single_mut() else {
+        return;
+    };
+
+    let storing = keyboard_input.pressed(controls.bookmark_store_modifier);
+
+    for (slot, &key) in controls.bookmark_slot_keys.iter().enumerate() {
+        if !keyboard_input.just_pressed(key) {
+            continue;
+        }
+
+        if storing {
+            bookmarks.slots[slot] = Some(CameraBookmark {
+                translation: transform.translation,
+                target_height: controller.zoom.target_height,
+                yaw: controller.yaw,
+            });
+        } else if let Some(bookmark) = bookmarks.slots[slot] {
+            bookmarks.cycle_index = slot;
+            recall_bookmark(&mut controller, bookmark);
+        }
+    }
+
+    if keyboard_input.just_pressed(controls.bookmark_cycle_key) {
+        cycle_bookmark(&mut bookmarks, &mut controller);
+    }
+}
+
+fn recall_bookmark(controller: &mut CameraController, bookmark: CameraBookmark) {
+    controller.zoom.target_height = bookmark.target_height;
+    controller.bookmark_target = Some(CameraBookmarkTarget {
+        translation: bookmark.translation,
+        yaw: bookmark.yaw,
+    });
+}
+
+fn cycle_bookmark(bookmarks: &mut CameraBookmarks, controller: &mut CameraController) {
+    for offset in 1..=BOOKMARK_SLOTS {
+        let index = (bookmarks.cycle_index + offset) % BOOKMARK_SLOTS;
+        if let Some(bookmark) = bookmarks.slots[index] {
+            bookmarks.cycle_index = index;
+            recall_bookmark(controller, bookmark);
+            return;
+        }
+    }
+}
+
+/// Eases translation (on the ground plane) and yaw toward a pending
+/// bookmark recall, reusing the same lerp `zoom_handler` already applies
+/// to height, and clears the target once close enough to it.
+fn apply_bookmark_recall(
+    controls: Res<CameraControls>,
+    mut query: Query<(&mut CameraController, &mut Transform)>,
+) {
+    for (mut controller, mut transform) in query.iter_mut() {
+        let Some(target) = controller.bookmark_target else {
+            continue;
+        };
+
+        let current_xz = Vec3::new(transform.translation.x, 0.0, transform.translation.z);
+        let target_xz = Vec3::new(target.translation.x, 0.0, target.translation.z);
+        let eased_xz = current_xz.lerp(target_xz, controls.smooth_factor);
+
+        transform.translation.x = eased_xz.x;
+        transform.translation.z = eased_xz.z;
+        controller.yaw = lerp(controller.yaw, target.yaw, controls.smooth_factor);
+
+        let arrived = current_xz.distance_squared(target_xz) < BOOKMARK_EASE_EPSILON_SQ
+            && (controller.yaw - target.yaw).abs() < BOOKMARK_YAW_EPSILON;
+
+        if arrived {
+            transform.translation.x = target.translation.x;
+            transform.translation.z = target.translation.z;
+            controller.yaw = target.yaw;
+            controller.bookmark_target = None;
+        }
+    }
+}
+
+/// While a rotate key is held, spins the camera around the ground point
+/// under the screen center rather than around the camera's own origin, so
+/// the view orbits its focus instead of just turning in place.
+fn camera_rotation(
+    time: Res<Time>,
+    controls: Res<CameraControls>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
     window: Query<&Window>,
+    mut query: Query<(&mut CameraController, &mut Transform, &GlobalTransform, &Camera)>,
+) {
+    let mut yaw_delta = 0.0;
+    if keyboard_input.pressed(controls.rotate_left) {
+        yaw_delta += controls.rotation_speed * time.delta_secs();
+    }
+    if keyboard_input.pressed(controls.rotate_right) {
+        yaw_delta -= controls.rotation_speed * time.delta_secs();
+    }
+
+    if yaw_delta == 0.0 {
+        return;
+    }
+
+    let window = window.single();
+    let screen_center = Vec2::new(window.width() / 2.0, window.height() / 2.0);
+
+    for (mut controller, mut transform, global_transform, camera) in query.iter_mut() {
+        let Ok(ray) = camera.viewport_to_world(global_transform, screen_center) else {
+            continue;
+        };
+        let Some(pivot) = ray_intersect_plane(ray, Vec3::Y, 0.0) else {
+            continue;
+        };
+
+        controller.yaw += yaw_delta;
+
+        let rotation = Quat::from_rotation_y(yaw_delta);
+        transform.translation = pivot + rotation * (transform.translation - pivot);
+    }
+}
+
+/// Re-raycasts the dragged ground point every frame (rather than anchoring
+/// to the drag's start position) and turns the resulting delta straight
+/// into `velocity`, so the last frame or two of drag before release is
+/// still sitting in `velocity` when the mouse button comes up, producing a
+/// natural "throw". Also swaps the window's cursor to
+/// `CameraControls::grabbing_cursor_icon` for the duration of the drag,
+/// restoring whatever icon was showing beforehand on release.
+fn camera_drag_movement(
+    time: Res<Time>,
+    controls: Res<CameraControls>,
+    mut commands: Commands,
+    window: Query<(Entity, &Window, Option<&CursorIcon>)>,
     mouse_input: Res<ButtonInput<MouseButton>>,
     mut drag_state: ResMut<CameraDragState>,
-    mut query: Query<(&mut Transform, &GlobalTransform, &Camera)>,
+    mut query: Query<(&mut CameraController, &GlobalTransform, &Camera)>,
 ) {
-    let window = window.single();
-    let (mut transform, global_transform, camera) = query.single_mut();
+    let (window_entity, window, current_cursor_icon) = window.single();
+    let (mut controller, global_transform, camera) = query.single_mut();
 
-    if mouse_input.just_pressed(MouseButton::Right) {
+    if mouse_input.just_pressed(controls.drag_button) {
         if let Some(cursor_position) = window.cursor_position() {
             if let Ok(ray) = camera.viewport_to_world(global_transform, cursor_position) {
                 if let Some(world_position) = ray_intersect_plane(ray, Vec3::Y, 0.0) {
                     drag_state.is_dragging = true;
-                    drag_state.drag_start_world_position = Some(world_position);
+                    drag_state.last_world_position = Some(world_position);
+                    drag_state.previous_cursor_icon = current_cursor_icon.cloned();
+                    commands.entity(window_entity).insert(controls.grabbing_cursor_icon.clone());
                 }
             }
         }
     }
 
-    if mouse_input.just_released(MouseButton::Right) {
+    if mouse_input.just_released(controls.drag_button) {
         drag_state.is_dragging = false;
-        drag_state.drag_start_world_position = None;
+        drag_state.last_world_position = None;
+        let restored_icon = drag_state.previous_cursor_icon.take().unwrap_or_else(|| controls.grab_cursor_icon.clone());
+        commands.entity(window_entity).insert(restored_icon);
     }
 
     if drag_state.is_dragging {
+        let dt = time.delta_secs();
         if let Some(cursor_position) = window.cursor_position() {
-            if let Some(start_world_pos) = drag_state.drag_start_world_position {
+            if let Some(last_world_pos) = drag_state.last_world_position {
                 if let Ok(ray) = camera.viewport_to_world(global_transform, cursor_position) {
                     if let Some(current_world_pos) = ray_intersect_plane(ray, Vec3::Y, 0.0) {
-                        let world_delta = start_world_pos - current_world_pos;
-
+                        let world_delta = last_world_pos - current_world_pos;
                         let movement = Vec3::new(world_delta.x, 0.0, world_delta.z);
-                        let new_position = transform.translation + movement;
-                        transform.translation = clamp_camera_position(new_position);
+
+                        if dt > f32::EPSILON {
+                            controller.velocity = movement / dt;
+                            controller.bookmark_target = None;
+                        }
+
+                        drag_state.last_world_position = Some(current_world_pos);
                     }
                 }
             }
@@ -119,32 +458,50 @@ const MAX_TILT: f32 = -1.35;
 
 pub fn zoom_handler(
     time: Res<Time>,
+    controls: Res<CameraControls>,
     mut mouse_wheel_events: EventReader<MouseWheel>,
-    mut query: Query<(&mut CameraController, &mut Transform)>,
+    mut query: Query<(&mut CameraController, &mut Transform, &mut Projection)>,
 ) {
+    let zoom_sign: f32 = if controls.invert_zoom { 1.0 } else { -1.0 };
     let mut scroll = 0.0;
     for event in mouse_wheel_events.read() {
-        scroll -= event.y;
+        scroll += zoom_sign * event.y;
     }
 
-    for (mut controller, mut transform) in query.iter_mut() {
-        controller.zoom.target_height -= scroll * controller.zoom.speed * time.delta_secs();
-        controller.zoom.target_height = controller.zoom.target_height.clamp(MIN_HEIGHT, MAX_HEIGHT);
+    for (mut controller, mut transform, mut projection) in query.iter_mut() {
+        if matches!(controller.zoom.mode, ZoomMode::Height | ZoomMode::Both) {
+            controller.zoom.target_height -= scroll * controls.zoom_speed * time.delta_secs();
+            controller.zoom.target_height = controller.zoom.target_height.clamp(MIN_HEIGHT, MAX_HEIGHT);
 
-        if controller.zoom.target_height == controller.zoom.current_height {
-            continue;
+            if controller.zoom.target_height != controller.zoom.current_height {
+                controller.zoom.current_height = lerp(
+                    controller.zoom.current_height,
+                    controller.zoom.target_height,
+                    controls.smooth_factor
+                );
+                transform.translation.y = controller.zoom.current_height;
+            }
         }
 
-        controller.zoom.current_height = lerp(
-            controller.zoom.current_height,
-            controller.zoom.target_height,
-            controller.zoom.smooth_factor
-        );
+        if matches!(controller.zoom.mode, ZoomMode::Fov | ZoomMode::Both) {
+            controller.zoom.target_fov -= scroll * controls.fov_zoom_speed * time.delta_secs();
+            controller.zoom.target_fov = controller.zoom.target_fov.clamp(controls.min_fov, controls.max_fov);
+
+            if controller.zoom.target_fov != controller.zoom.current_fov {
+                controller.zoom.current_fov = lerp(
+                    controller.zoom.current_fov,
+                    controller.zoom.target_fov,
+                    controls.smooth_factor
+                );
+
+                if let Projection::Perspective(perspective) = projection.as_mut() {
+                    perspective.fov = controller.zoom.current_fov;
+                }
+            }
+        }
 
-        transform.translation.y = controller.zoom.current_height;
         let pitch_angle = height_to_tilt(controller.zoom.current_height);
-        let (yaw, _, roll) = transform.rotation.to_euler(EulerRot::YXZ);
-        transform.rotation = Quat::from_euler(EulerRot::YXZ, yaw, pitch_angle, roll);
+        transform.rotation = Quat::from_euler(EulerRot::YXZ, controller.yaw, pitch_angle, 0.0);
     }
 }
 
@@ -161,30 +518,121 @@ fn lerp(start: f32, end: f32, t: f32) -> f32 {
 }
 
 fn camera_movement(
-    time: Res<Time>,
+    controls: Res<CameraControls>,
     keyboard_input: Res<ButtonInput<KeyCode>>,
-    mut query: Query<(&CameraController, &mut Transform)>,
+    drag_state: Res<CameraDragState>,
+    edge_pan_enabled: Res<EdgePanEnabled>,
+    window: Query<&Window>,
+    mut query: Query<&mut CameraController>,
 ) {
-    for (controller, mut transform) in query.iter_mut() {
-        let mut direction = Vec3::ZERO;
+    let mut wasd_direction = Vec3::ZERO;
+
+    if keyboard_input.pressed(controls.pan_forward) {
+        wasd_direction.z -= 1.0;
+    }
+    if keyboard_input.pressed(controls.pan_backward) {
+        wasd_direction.z += 1.0;
+    }
+    if keyboard_input.pressed(controls.pan_left) {
+        wasd_direction.x -= 1.0;
+    }
+    if keyboard_input.pressed(controls.pan_right) {
+        wasd_direction.x += 1.0;
+    }
+
+    let mut velocity = if wasd_direction != Vec3::ZERO {
+        wasd_direction.normalize() * controls.movement_speed
+    } else {
+        Vec3::ZERO
+    };
 
-        if keyboard_input.pressed(KeyCode::KeyW) {
-            direction.z -= 1.0;
+    if edge_pan_enabled.0 && !drag_state.is_dragging {
+        if let Ok(window) = window.get_single() {
+            if let Some(cursor) = window.cursor_position() {
+                velocity += edge_pan_velocity(cursor, window.width(), window.height(), &controls);
+            }
         }
-        if keyboard_input.pressed(KeyCode::KeyS) {
-            direction.z += 1.0;
+    }
+
+    for mut controller in query.iter_mut() {
+        controller.driven_this_tick = velocity != Vec3::ZERO;
+        if velocity != Vec3::ZERO {
+            controller.velocity = velocity;
+            controller.bookmark_target = None;
         }
-        if keyboard_input.pressed(KeyCode::KeyA) {
-            direction.x -= 1.0;
+    }
+}
+
+/// The edge-pan contribution to camera velocity: zero while the cursor
+/// sits further than `edge_pan_margin` pixels from every border, ramping
+/// linearly up to `edge_pan_speed` right at the border.
+fn edge_pan_velocity(cursor: Vec2, width: f32, height: f32, controls: &CameraControls) -> Vec3 {
+    let margin = controls.edge_pan_margin;
+    if margin <= 0.0 {
+        return Vec3::ZERO;
+    }
+
+    let mut direction = Vec3::ZERO;
+    let mut factor: f32 = 0.0;
+
+    if cursor.x < margin {
+        direction.x -= 1.0;
+        factor = factor.max((margin - cursor.x) / margin);
+    } else if cursor.x > width - margin {
+        direction.x += 1.0;
+        factor = factor.max((cursor.x - (width - margin)) / margin);
+    }
+
+    if cursor.y < margin {
+        direction.z -= 1.0;
+        factor = factor.max((margin - cursor.y) / margin);
+    } else if cursor.y > height - margin {
+        direction.z += 1.0;
+        factor = factor.max((cursor.y - (height - margin)) / margin);
+    }
+
+    if direction == Vec3::ZERO {
+        return Vec3::ZERO;
+    }
+
+    direction.normalize() * controls.edge_pan_speed * factor.clamp(0.0, 1.0)
+}
+
+/// Integrates `velocity` into position every fixed tick and, when neither
+/// WASD/edge-pan nor drag drove it this frame, decays it by `friction` so
+/// the camera glides to a stop instead of stopping dead. Hitting a
+/// map-edge clamp zeroes the velocity axis that caused it, so the camera
+/// doesn't keep shoving against the boundary.
+fn integrate_camera_velocity(
+    time: Res<Time>,
+    drag_state: Res<CameraDragState>,
+    mut query: Query<(&mut CameraController, &mut Transform)>,
+) {
+    let dt = time.delta_secs();
+
+    for (mut controller, mut transform) in query.iter_mut() {
+        let input_active = drag_state.is_dragging || controller.driven_this_tick;
+
+        if !input_active {
+            controller.velocity *= (1.0 - controller.friction * dt).clamp(0.0, 1.0);
+            if controller.velocity.length_squared() < VELOCITY_EPSILON_SQ {
+                controller.velocity = Vec3::ZERO;
+            }
         }
-        if keyboard_input.pressed(KeyCode::KeyD) {
-            direction.x += 1.0;
+
+        if controller.velocity == Vec3::ZERO {
+            continue;
         }
 
-        if direction != Vec3::ZERO {
-            direction = direction.normalize();
-            let new_position = transform.translation + direction * 440.0 * time.delta_secs();
-            transform.translation = clamp_camera_position(new_position);
+        let new_position = transform.translation + controller.velocity * dt;
+        let clamped_position = clamp_camera_position(new_position);
+        transform.translation = clamped_position;
+
+        if clamped_position.x != new_position.x {
+            controller.velocity.x = 0.0;
+        }
+        if clamped_position.z != new_position.z {
+            controller.velocity.z = 0.0;
         }
     }
 }
@@ -1,8 +1,7 @@
-use bevy::app::{App, Startup};
-use crate::world::map::terrain::spawn_terrain_chunks;
+use bevy::app::App;
 
-mod terrain;
+pub(crate) mod terrain;
 
 pub fn build(app: &mut App) {
-    app.add_systems(Startup, spawn_terrain_chunks);
+    terrain::build(app);
 }
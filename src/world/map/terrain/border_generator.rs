@@ -1,6 +1,7 @@
-use std::cmp::PartialEq;
 use image::GrayImage;
-use crate::world::map::terrain::terrain_generator::{HEIGHT_SCALE, VOID_HEIGHT};
+use crate::world::map::terrain::terrain_generator::{block_is_solid, calculate_vertex_height, HEIGHT_SCALE};
+
+const WALL_DEPTH: f32 = 8.0 * HEIGHT_SCALE;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum Direction {
@@ -10,25 +11,15 @@ enum Direction {
     Bottom
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-enum BorderType {
-    Corner,
-    Line,
-    DeadEnd,
-    Canyon,
-    Pit,
-    None
-}
-
-struct RelativeCoord {
-    forward: i32,
-    right: i32,
-}
-
+/// Walks the chunk one `step`x`step` block at a time (the same coarse grid
+/// `generate_terrain_mesh` builds the visible surface from at this LOD) and
+/// extrudes a wall everywhere a solid block borders a void one, so wall tops
+/// land exactly on the LOD'd terrain edge instead of the full-res surface.
 pub fn make_borders(
     start_x: u32,
     start_z: u32,
     chunk_size: u32,
+    step: u32,
     heightmap: &GrayImage,
     positions: &mut Vec<[f32; 3]>,
     normals: &mut Vec<[f32; 3]>,
@@ -38,133 +29,46 @@ pub fn make_borders(
     let end_x = (start_x + chunk_size).min(heightmap.width());
     let end_z = (start_z + chunk_size).min(heightmap.height());
 
-    for z in start_z..end_z {
-        for x in start_x..end_x {
-            if heightmap.get_pixel(x, z)[0] == VOID_HEIGHT {
-                continue;
-            }
-
-            if is_border_pixel(x, z, heightmap) {
-                if x > 0 && heightmap.get_pixel(x-1, z)[0] == VOID_HEIGHT {
-                    process_border(x-1, z, Direction::Left, heightmap, positions, normals, uvs, indices);
-                }
-                if x+1 < heightmap.width() && heightmap.get_pixel(x+1, z)[0] == VOID_HEIGHT {
-                    process_border(x+1, z, Direction::Right, heightmap, positions, normals, uvs, indices);
-                }
-                if z > 0 && heightmap.get_pixel(x, z-1)[0] == VOID_HEIGHT {
-                    process_border(x, z-1, Direction::Top, heightmap, positions, normals, uvs, indices);
-                }
-                if z+1 < heightmap.height() && heightmap.get_pixel(x, z+1)[0] == VOID_HEIGHT {
-                    process_border(x, z+1, Direction::Bottom, heightmap, positions, normals, uvs, indices);
+    let mut z = start_z;
+    while z < end_z {
+        let mut x = start_x;
+        while x < end_x {
+            if block_is_solid(x, z, step, heightmap) {
+                if is_border_block(x, z, step, heightmap) {
+                    if x >= step && !block_is_solid(x - step, z, step, heightmap) {
+                        process_line_border(x - step, z, Direction::Left, start_x, start_z, step, positions, normals, uvs, indices, heightmap);
+                    }
+                    if x + step < heightmap.width() && !block_is_solid(x + step, z, step, heightmap) {
+                        process_line_border(x + step, z, Direction::Right, start_x, start_z, step, positions, normals, uvs, indices, heightmap);
+                    }
+                    if z >= step && !block_is_solid(x, z - step, step, heightmap) {
+                        process_line_border(x, z - step, Direction::Top, start_x, start_z, step, positions, normals, uvs, indices, heightmap);
+                    }
+                    if z + step < heightmap.height() && !block_is_solid(x, z + step, step, heightmap) {
+                        process_line_border(x, z + step, Direction::Bottom, start_x, start_z, step, positions, normals, uvs, indices, heightmap);
+                    }
                 }
             }
-        }
-    }
-}
-
-fn process_border(
-    x: u32,
-    z: u32,
-    direction: Direction,
-    heightmap: &GrayImage,
-    positions: &mut Vec<[f32; 3]>,
-    normals: &mut Vec<[f32; 3]>,
-    uvs: &mut Vec<[f32; 2]>,
-    indices: &mut Vec<u32>
-) {
-    let border_type = determine_border_type(x, z, direction, heightmap);
-
-    match border_type {
-        BorderType::Line => process_line_border(x, z, direction, positions, normals, uvs, indices, heightmap),
-        _ => println!("Border at x: {}, z: {}, direction: {:?}, type: {:?}", x, z, direction, border_type)
-    }
-}
-
-fn determine_border_type(x: u32, z: u32, direction: Direction, heightmap: &GrayImage) -> BorderType {
-    let forward = check_relative_pixel(x, z, direction, RelativeCoord { forward: 1, right: 0 }, heightmap);
-    let right = check_relative_pixel(x, z, direction, RelativeCoord { forward: 0, right: 1 }, heightmap);
-    let left = check_relative_pixel(x, z, direction, RelativeCoord { forward: 0, right: -1 }, heightmap);
-
-    if !forward && right && !left {
-        BorderType::Corner
-    } else if !forward && !right && !left {
-        BorderType::Line
-    } else {
-        BorderType::None
-    }
-}
-
-fn check_relative_pixel(
-    base_x: u32,
-    base_z: u32,
-    direction: Direction,
-    rel: RelativeCoord,
-    heightmap: &GrayImage
-) -> bool {
-    let pixel = get_relative_pixel(base_x, base_z, direction, rel, heightmap);
-
-    if pixel.is_none() {
-        return false;
-    }
-
-    pixel.unwrap() > VOID_HEIGHT
-}
-
-fn get_relative_pixel(
-    base_x: u32,
-    base_z: u32,
-    direction: Direction,
-    rel: RelativeCoord,
-    heightmap: &GrayImage
-) -> Option<u8> {
-    let (nx, nz) = relative_to_absolute(base_x, base_z, direction, rel);
 
-    if nx >= heightmap.width() || nz >= heightmap.height() || nx < 0 || nz < 0 {
-        return None;
-    }
-    if (base_x == 1503 && base_z == 3433) {
-        println!("x {:?}, y {:?}, dir {:?}, height {:?}", nx, nz, direction, heightmap.get_pixel(nx, nz)[0]);
+            x += step;
+        }
+        z += step;
     }
-
-    Some(heightmap.get_pixel(nx, nz)[0])
-}
-
-fn relative_to_absolute(
-    base_x: u32,
-    base_z: u32,
-    direction: Direction,
-    rel: RelativeCoord
-) -> (u32, u32) {
-    let (dx, dz) = match direction {
-        Direction::Right => (-rel.right, -rel.forward), // 1, 1 -> 1, -1
-        Direction::Left => (rel.right, rel.forward), // 1, 1 -> -1, 1
-        Direction::Bottom => (-rel.right, rel.forward), // 1, 1 -> 1, 1
-        Direction::Top => (rel.right, -rel.forward), // 1, 1 -> 1, 1
-    };
-
-    let abs_x = if dx < 0 && base_x < dx.abs() as u32 {
-        0
-    } else {
-        (base_x as i32 + dx) as u32
-    };
-
-    let abs_z = if dz < 0 && base_z < dz.abs() as u32 {
-        0
-    } else {
-        (base_z as i32 + dz) as u32
-    };
-
-    (abs_x, abs_z)
 }
 
-fn is_border_pixel(x: u32, z: u32, heightmap: &GrayImage) -> bool {
+fn is_border_block(x: u32, z: u32, step: u32, heightmap: &GrayImage) -> bool {
     let directions = [(0, -1), (1, 0), (0, 1), (-1, 0)];
 
     for (dx, dz) in directions {
-        let nx = (x as i32 + dx) as u32;
-        let nz = (z as i32 + dz) as u32;
+        let nx = x as i32 + dx * step as i32;
+        let nz = z as i32 + dz * step as i32;
 
-        if nx < heightmap.width() && nz < heightmap.height() && heightmap.get_pixel(nx, nz)[0] == VOID_HEIGHT {
+        if nx < 0 || nz < 0 {
+            continue;
+        }
+
+        let (nx, nz) = (nx as u32, nz as u32);
+        if nx < heightmap.width() && nz < heightmap.height() && !block_is_solid(nx, nz, step, heightmap) {
             return true;
         }
     }
@@ -172,17 +76,103 @@ fn is_border_pixel(x: u32, z: u32, heightmap: &GrayImage) -> bool {
     false
 }
 
+/// The void block `(void_x, void_z)` sits one `step` past the solid ground
+/// in `direction`; the two together share the grid edge this wall extrudes
+/// down from. Every bordering void block gets this same quad, regardless of
+/// the shape of the void region it faces.
 fn process_line_border(
-    x: u32,
-    z: u32,
+    void_x: u32,
+    void_z: u32,
     direction: Direction,
+    start_x: u32,
+    start_z: u32,
+    step: u32,
     positions: &mut Vec<[f32; 3]>,
     normals: &mut Vec<[f32; 3]>,
     uvs: &mut Vec<[f32; 2]>,
     indices: &mut Vec<u32>,
     heightmap: &GrayImage
 ) {
-    let height = heightmap.get_pixel(x, z)[0] as f32 * HEIGHT_SCALE;
+    let (edge_a, edge_b, normal) = wall_edge(void_x, void_z, direction, step);
+    append_wall_quad(edge_a, edge_b, normal, start_x, start_z, step, heightmap, positions, normals, uvs, indices);
+}
+
+/// The grid-corner endpoints of the edge between void block `(void_x,
+/// void_z)` and its solid neighbor in `direction`, plus the normal pointing
+/// from solid ground out into the void. The endpoints are ordered so that
+/// `append_wall_quad`'s winding puts the quad's front face on the `normal`
+/// side.
+fn wall_edge(void_x: u32, void_z: u32, direction: Direction, step: u32) -> ((u32, u32), (u32, u32), [f32; 3]) {
+    match direction {
+        Direction::Left => {
+            let boundary_x = void_x + step;
+            ((boundary_x, void_z + step), (boundary_x, void_z), [-1.0, 0.0, 0.0])
+        }
+        Direction::Right => {
+            let boundary_x = void_x;
+            ((boundary_x, void_z), (boundary_x, void_z + step), [1.0, 0.0, 0.0])
+        }
+        Direction::Top => {
+            let boundary_z = void_z + step;
+            ((void_x, boundary_z), (void_x + step, boundary_z), [0.0, 0.0, -1.0])
+        }
+        Direction::Bottom => {
+            let boundary_z = void_z;
+            ((void_x + step, boundary_z), (void_x, boundary_z), [0.0, 0.0, 1.0])
+        }
+    }
+}
+
+/// Extrudes a quad from the terrain edge (`edge_a`-`edge_b`, at the height
+/// the terrain mesh already uses for those same corners) down to
+/// `WALL_DEPTH`, facing `normal`.
+fn append_wall_quad(
+    edge_a: (u32, u32),
+    edge_b: (u32, u32),
+    normal: [f32; 3],
+    start_x: u32,
+    start_z: u32,
+    step: u32,
+    heightmap: &GrayImage,
+    positions: &mut Vec<[f32; 3]>,
+    normals: &mut Vec<[f32; 3]>,
+    uvs: &mut Vec<[f32; 2]>,
+    indices: &mut Vec<u32>
+) {
+    let height_a = calculate_vertex_height(edge_a.0, edge_a.1, step, heightmap);
+    let height_b = calculate_vertex_height(edge_b.0, edge_b.1, step, heightmap);
+
+    let local_a = local_xz(edge_a, start_x, start_z);
+    let local_b = local_xz(edge_b, start_x, start_z);
+
+    let base_index = positions.len() as u32;
+
+    positions.push([local_a.0, height_a, local_a.1]);
+    positions.push([local_b.0, height_b, local_b.1]);
+    positions.push([local_b.0, height_b - WALL_DEPTH, local_b.1]);
+    positions.push([local_a.0, height_a - WALL_DEPTH, local_a.1]);
+
+    for _ in 0..4 {
+        normals.push(normal);
+    }
+
+    uvs.push([0.0, 0.0]);
+    uvs.push([1.0, 0.0]);
+    uvs.push([1.0, 1.0]);
+    uvs.push([0.0, 1.0]);
+
+    indices.push(base_index);
+    indices.push(base_index + 1);
+    indices.push(base_index + 2);
+
+    indices.push(base_index);
+    indices.push(base_index + 2);
+    indices.push(base_index + 3);
+}
 
-    println!("Created Line border at x: {}, z: {}, direction: {:?}", x, z, direction);
+fn local_xz(coord: (u32, u32), start_x: u32, start_z: u32) -> (f32, f32) {
+    (
+        (coord.0 as i32 - start_x as i32) as f32,
+        (coord.1 as i32 - start_z as i32) as f32,
+    )
 }
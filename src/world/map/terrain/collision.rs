@@ -0,0 +1,20 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use image::GrayImage;
+use crate::world::map::terrain::terrain_generator::generate_collision_mesh;
+
+pub(crate) fn build(app: &mut App) {
+    app.add_plugins(RapierPhysicsPlugin::<NoUserData>::default());
+}
+
+/// Builds a static trimesh collider for a chunk from its original-chunk
+/// triangles only, so the LOD skirt never produces overlapping collision
+/// surfaces with its neighbor.
+pub(crate) fn chunk_collider(start_x: u32, start_z: u32, heightmap: &GrayImage, lod: u32) -> Option<Collider> {
+    let (positions, indices) = generate_collision_mesh(start_x, start_z, heightmap, lod)?;
+
+    let vertices: Vec<Vec3> = positions.into_iter().map(Vec3::from).collect();
+    let triangles: Vec<[u32; 3]> = indices.chunks_exact(3).map(|c| [c[0], c[1], c[2]]).collect();
+
+    Some(Collider::trimesh(vertices, triangles))
+}
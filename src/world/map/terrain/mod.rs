@@ -1,36 +1,64 @@
 mod terrain_generator;
 mod border_generator;
+mod terrain_normals;
+mod picking;
+mod streaming;
+mod collision;
 
 use std::f32::consts::PI;
 use std::path::Path;
 use bevy::color::palettes::basic::WHITE;
-use bevy::pbr::wireframe::Wireframe;
 use bevy::prelude::*;
-use bevy::render::view::RenderLayers;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
 use image::{GrayImage, ImageReader};
-use crate::world::map::terrain::terrain_generator::generate_terrain_mesh;
+use crate::world::map::terrain::terrain_normals::{HeightmapNormalsSource, TerrainNormalsPlugin};
 
-pub fn spawn_terrain_chunks(
+pub use picking::raycast_heightmap;
+
+pub(crate) const CHUNK_SIZE: u32 = 128;
+pub(crate) const MAP_WIDTH: u32 = 8192;
+pub(crate) const MAP_HEIGHT: u32 = 4096;
+
+/// The decoded heightmap, kept around as a resource so gameplay systems
+/// (terrain picking, camera raycasts) can query it without re-reading the
+/// image from disk.
+#[derive(Resource)]
+pub struct TerrainHeightmap(pub GrayImage);
+
+/// Parent entity all streamed-in terrain chunks are attached to.
+#[derive(Resource)]
+pub(crate) struct TerrainRoot(pub Entity);
+
+pub fn build(app: &mut App) {
+    app.add_plugins(TerrainNormalsPlugin);
+    collision::build(app);
+    app.add_systems(Startup, (setup_terrain, streaming::init_terrain_material));
+    app.add_systems(Update, streaming::stream_terrain_chunks);
+    app.init_resource::<streaming::LiveChunks>();
+}
+
+fn setup_terrain(
     mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut images: ResMut<Assets<Image>>,
 ) {
-    let width = 8192;
-    let height = 4096;
-    let chunk_size = 128;
+    let heightmap = load_heightmap("common/map/heightmap.png");
 
-    let num_chunks_x = width / chunk_size;
-    let num_chunks_z = height / chunk_size;
+    commands.insert_resource(TerrainHeightmap(heightmap.clone()));
 
-    let heightmap = load_heightmap("common/map/heightmap.png");
+    let heightmap_texture = images.add(heightmap_to_image(&heightmap));
+    commands.insert_resource(HeightmapNormalsSource {
+        texture: heightmap_texture,
+        width: heightmap.width(),
+        height: heightmap.height(),
+    });
 
     let parent_entity = commands.spawn((
         Transform::default(),
         GlobalTransform::default(),
         Visibility::default()
     )).id();
-
-    let mut chunk_num_id = 0;
+    commands.insert_resource(TerrainRoot(parent_entity));
 
     commands.spawn((
         DirectionalLight {
@@ -42,40 +70,6 @@ pub fn spawn_terrain_chunks(
         },
         Transform::from_xyz(0.0, 2000.0, 0.0).with_rotation(Quat::from_axis_angle(Vec3::ONE, -PI / 6.))
     ));
-
-    for z in 0..num_chunks_z {
-        for x in 0..num_chunks_x {
-            let start_x = x * chunk_size;
-            let start_z = z * chunk_size;
-
-            let material_handle = materials.add(StandardMaterial {
-                base_color: Color::srgb(0.3, 0.5, 0.4),
-                perceptual_roughness: 1.0,
-                ..default()
-            });
-
-            let mesh = generate_terrain_mesh(start_x, start_z, &heightmap);
-            if mesh.is_none() {
-                continue;
-            }
-
-            let terrain_chunk = commands.spawn((
-                Mesh3d::from(meshes.add(mesh.unwrap())),
-                MeshMaterial3d::from(material_handle),
-                Transform {
-                    translation: Vec3::new(start_x as f32, 0.0, start_z as f32),
-                    scale: Vec3::new(1.0, 1.0, 1.0),
-                    ..default()
-                },
-                Wireframe,
-                RenderLayers::from_layers(&[0, 1])
-            )).id();
-
-            commands.entity(parent_entity).insert_children(chunk_num_id as usize, &[terrain_chunk]);
-
-            chunk_num_id += 1;
-        }
-    }
 }
 
 fn load_heightmap(path: &str) -> GrayImage {
@@ -86,3 +80,21 @@ fn load_heightmap(path: &str) -> GrayImage {
 
     img.into_luma8()
 }
+
+/// Uploads the heightmap as a single-channel float texture so the normals
+/// compute shader can sample it directly.
+fn heightmap_to_image(heightmap: &GrayImage) -> Image {
+    let data: Vec<u8> = heightmap.pixels().flat_map(|p| (p[0] as f32 / 255.0).to_le_bytes()).collect();
+
+    Image::new(
+        Extent3d {
+            width: heightmap.width(),
+            height: heightmap.height(),
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::R32Float,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+}
@@ -0,0 +1,103 @@
+use bevy::prelude::*;
+use image::GrayImage;
+use crate::world::map::terrain::terrain_generator::{calculate_vertex_height, VOID_HEIGHT};
+
+/// Marches `origin + t * dir` cell-by-cell across the heightmap's XZ grid
+/// (2D DDA) and returns the first point where the ray crosses the terrain
+/// surface going down. Within each cell the surface is taken as flat, at the
+/// height `calculate_vertex_height` gives the cell's `(x, z)` corner; it is
+/// not interpolated across the cell. Used to turn a screen-space cursor ray
+/// into a world position for unit selection and placement.
+pub fn raycast_heightmap(origin: Vec3, dir: Vec3, heightmap: &GrayImage) -> Option<Vec3> {
+    let width = heightmap.width() as i32;
+    let height = heightmap.height() as i32;
+
+    if dir.x == 0.0 && dir.z == 0.0 {
+        return None;
+    }
+
+    let mut cell_x = origin.x.floor() as i32;
+    let mut cell_z = origin.z.floor() as i32;
+
+    let step_x: i32 = if dir.x > 0.0 { 1 } else if dir.x < 0.0 { -1 } else { 0 };
+    let step_z: i32 = if dir.z > 0.0 { 1 } else if dir.z < 0.0 { -1 } else { 0 };
+
+    let t_delta_x = if dir.x != 0.0 { (1.0 / dir.x).abs() } else { f32::INFINITY };
+    let t_delta_z = if dir.z != 0.0 { (1.0 / dir.z).abs() } else { f32::INFINITY };
+
+    let mut t_max_x = if dir.x > 0.0 {
+        ((cell_x + 1) as f32 - origin.x) / dir.x
+    } else if dir.x < 0.0 {
+        (cell_x as f32 - origin.x) / dir.x
+    } else {
+        f32::INFINITY
+    };
+
+    let mut t_max_z = if dir.z > 0.0 {
+        ((cell_z + 1) as f32 - origin.z) / dir.z
+    } else if dir.z < 0.0 {
+        (cell_z as f32 - origin.z) / dir.z
+    } else {
+        f32::INFINITY
+    };
+
+    let mut t = 0.0f32;
+    let max_distance = (width as f32).hypot(height as f32);
+
+    while t < max_distance {
+        let next_t = t_max_x.min(t_max_z);
+
+        if cell_x >= 0 && cell_z >= 0 && cell_x < width && cell_z < height {
+            if heightmap.get_pixel(cell_x as u32, cell_z as u32)[0] != VOID_HEIGHT {
+                if let Some(hit) = test_cell_crossing(origin, dir, cell_x as u32, cell_z as u32, t, next_t, heightmap) {
+                    return Some(hit);
+                }
+            }
+        }
+
+        if t_max_x < t_max_z {
+            t = t_max_x;
+            t_max_x += t_delta_x;
+            cell_x += step_x;
+        } else {
+            t = t_max_z;
+            t_max_z += t_delta_z;
+            cell_z += step_z;
+        }
+    }
+
+    None
+}
+
+fn test_cell_crossing(
+    origin: Vec3,
+    dir: Vec3,
+    cell_x: u32,
+    cell_z: u32,
+    t_enter: f32,
+    t_exit: f32,
+    heightmap: &GrayImage,
+) -> Option<Vec3> {
+    let surface_height = calculate_vertex_height(cell_x, cell_z, 1, heightmap);
+
+    let y_enter = origin.y + dir.y * t_enter;
+    let y_exit = origin.y + dir.y * t_exit;
+
+    if (y_enter - surface_height) * (y_exit - surface_height) > 0.0 {
+        return None;
+    }
+
+    // Only a downward crossing (above the surface on entry, at or below it on
+    // exit) counts as a pick; a ray grazing up through the terrain from below
+    // shouldn't report a hit on its underside.
+    if y_enter < surface_height {
+        return None;
+    }
+
+    if (y_enter - y_exit).abs() < f32::EPSILON {
+        return None;
+    }
+
+    let t_surface = t_enter + (y_enter - surface_height) / (y_enter - y_exit) * (t_exit - t_enter);
+    Some(origin + dir * t_surface)
+}
@@ -0,0 +1,214 @@
+use bevy::pbr::wireframe::Wireframe;
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+use bevy_rapier3d::prelude::RigidBody;
+use std::collections::{HashMap, HashSet};
+use crate::world::map::terrain::collision::chunk_collider;
+use crate::world::map::terrain::terrain_generator::{generate_terrain_mesh, LodNeighbors};
+use crate::world::map::terrain::terrain_normals::PackedHeightmapNormals;
+use crate::world::map::terrain::{TerrainHeightmap, TerrainRoot, CHUNK_SIZE, MAP_HEIGHT, MAP_WIDTH};
+
+const STREAMING_RADIUS_CHUNKS: i32 = 4;
+const MAX_LOD: u32 = 3;
+
+struct LiveChunk {
+    entity: Entity,
+    mesh_handle: Handle<Mesh>,
+    lod: u32,
+}
+
+/// Terrain chunks currently spawned, keyed by chunk grid coordinate. Each
+/// entry also keeps the chunk's mesh handle (to free it from `Assets<Mesh>`
+/// when the chunk leaves streaming range) and the LOD it was built at (to
+/// detect when distance to the camera demands a re-mesh).
+#[derive(Resource, Default)]
+pub(crate) struct LiveChunks(HashMap<(u32, u32), LiveChunk>);
+
+/// The one `StandardMaterial` every terrain chunk is spawned with. All
+/// chunks look identical, so chunks share this handle instead of each
+/// allocating (and leaking, once despawned) their own.
+#[derive(Resource)]
+pub(crate) struct TerrainMaterial(Handle<StandardMaterial>);
+
+pub(crate) fn init_terrain_material(mut commands: Commands, mut materials: ResMut<Assets<StandardMaterial>>) {
+    let handle = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.3, 0.5, 0.4),
+        perceptual_roughness: 1.0,
+        ..default()
+    });
+    commands.insert_resource(TerrainMaterial(handle));
+}
+
+pub(crate) fn stream_terrain_chunks(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    heightmap: Option<Res<TerrainHeightmap>>,
+    packed_normals: Option<Res<PackedHeightmapNormals>>,
+    terrain_root: Option<Res<TerrainRoot>>,
+    terrain_material: Option<Res<TerrainMaterial>>,
+    mut live_chunks: ResMut<LiveChunks>,
+    camera_query: Query<&Transform, With<Camera3d>>,
+) {
+    let (Some(heightmap), Some(terrain_root), Some(terrain_material)) = (heightmap, terrain_root, terrain_material) else {
+        return;
+    };
+    let Ok(camera_transform) = camera_query.get_single() else {
+        return;
+    };
+
+    // The GPU normals readback lands some frames after startup (dispatch ->
+    // blocking map -> channel -> this resource), after chunks nearest the
+    // camera may already be resident with the flat placeholder normal. The
+    // one time it transitions from empty to populated, force every resident
+    // chunk to respawn so it picks up the real normals.
+    if let Some(packed_normals) = &packed_normals {
+        if packed_normals.is_changed() && packed_normals.width > 0 {
+            despawn_all_chunks(&mut commands, &mut meshes, &mut live_chunks);
+        }
+    }
+
+    let desired_lods = desired_chunk_lods(camera_transform.translation);
+    let desired: HashSet<(u32, u32)> = desired_lods.keys().copied().collect();
+
+    despawn_stale_chunks(&mut commands, &mut meshes, &mut live_chunks, &desired_lods);
+    spawn_new_chunks(
+        &mut commands,
+        &mut meshes,
+        &heightmap.0,
+        packed_normals.as_deref(),
+        terrain_root.0,
+        &terrain_material.0,
+        &mut live_chunks,
+        &desired_lods,
+        &desired,
+    );
+}
+
+/// Maps every chunk coordinate the camera should currently see to the LOD
+/// it should be rendered at: 0 for the chunks right under the camera,
+/// increasing with ring distance, capped at `MAX_LOD`.
+fn desired_chunk_lods(camera_position: Vec3) -> HashMap<(u32, u32), u32> {
+    let num_chunks_x = (MAP_WIDTH / CHUNK_SIZE) as i32;
+    let num_chunks_z = (MAP_HEIGHT / CHUNK_SIZE) as i32;
+
+    let camera_chunk_x = (camera_position.x / CHUNK_SIZE as f32).floor() as i32;
+    let camera_chunk_z = (camera_position.z / CHUNK_SIZE as f32).floor() as i32;
+
+    let mut desired = HashMap::new();
+    for dz in -STREAMING_RADIUS_CHUNKS..=STREAMING_RADIUS_CHUNKS {
+        for dx in -STREAMING_RADIUS_CHUNKS..=STREAMING_RADIUS_CHUNKS {
+            let cx = camera_chunk_x + dx;
+            let cz = camera_chunk_z + dz;
+
+            if cx < 0 || cz < 0 || cx >= num_chunks_x || cz >= num_chunks_z {
+                continue;
+            }
+
+            let ring_distance = dx.abs().max(dz.abs());
+            let lod = (ring_distance as u32).min(MAX_LOD);
+            desired.insert((cx as u32, cz as u32), lod);
+        }
+    }
+
+    desired
+}
+
+fn despawn_stale_chunks(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    live_chunks: &mut LiveChunks,
+    desired_lods: &HashMap<(u32, u32), u32>,
+) {
+    let to_remove: Vec<(u32, u32)> = live_chunks.0.iter()
+        .filter(|(coord, chunk)| match desired_lods.get(coord) {
+            None => true,
+            Some(&lod) => lod != chunk.lod,
+        })
+        .map(|(coord, _)| *coord)
+        .collect();
+
+    for coord in to_remove {
+        if let Some(chunk) = live_chunks.0.remove(&coord) {
+            meshes.remove(&chunk.mesh_handle);
+            commands.entity(chunk.entity).despawn();
+        }
+    }
+}
+
+fn despawn_all_chunks(commands: &mut Commands, meshes: &mut Assets<Mesh>, live_chunks: &mut LiveChunks) {
+    for (_, chunk) in live_chunks.0.drain() {
+        meshes.remove(&chunk.mesh_handle);
+        commands.entity(chunk.entity).despawn();
+    }
+}
+
+fn spawn_new_chunks(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    heightmap: &image::GrayImage,
+    packed_normals: Option<&PackedHeightmapNormals>,
+    terrain_root: Entity,
+    terrain_material: &Handle<StandardMaterial>,
+    live_chunks: &mut LiveChunks,
+    desired_lods: &HashMap<(u32, u32), u32>,
+    desired: &HashSet<(u32, u32)>,
+) {
+    for &(cx, cz) in desired {
+        if live_chunks.0.contains_key(&(cx, cz)) {
+            continue;
+        }
+
+        let lod = desired_lods[&(cx, cz)];
+        let neighbor_lods = LodNeighbors {
+            left: coarser_neighbor_lod(desired_lods, cx.checked_sub(1), Some(cz), lod),
+            right: coarser_neighbor_lod(desired_lods, cx.checked_add(1), Some(cz), lod),
+            top: coarser_neighbor_lod(desired_lods, Some(cx), cz.checked_sub(1), lod),
+            bottom: coarser_neighbor_lod(desired_lods, Some(cx), cz.checked_add(1), lod),
+        };
+
+        let start_x = cx * CHUNK_SIZE;
+        let start_z = cz * CHUNK_SIZE;
+
+        let Some(mesh) = generate_terrain_mesh(start_x, start_z, heightmap, packed_normals, lod, neighbor_lods) else {
+            continue;
+        };
+
+        let mesh_handle = meshes.add(mesh);
+
+        let chunk_entity = commands.spawn((
+            Mesh3d::from(mesh_handle.clone()),
+            MeshMaterial3d::from(terrain_material.clone()),
+            Transform {
+                translation: Vec3::new(start_x as f32, 0.0, start_z as f32),
+                scale: Vec3::new(1.0, 1.0, 1.0),
+                ..default()
+            },
+            Wireframe,
+            RenderLayers::from_layers(&[0, 1])
+        )).id();
+
+        commands.entity(terrain_root).add_child(chunk_entity);
+
+        if let Some(collider) = chunk_collider(start_x, start_z, heightmap, lod) {
+            commands.entity(chunk_entity).insert((RigidBody::Fixed, collider));
+        }
+
+        live_chunks.0.insert((cx, cz), LiveChunk { entity: chunk_entity, mesh_handle, lod });
+    }
+}
+
+fn coarser_neighbor_lod(
+    desired_lods: &HashMap<(u32, u32), u32>,
+    nx: Option<u32>,
+    nz: Option<u32>,
+    own_lod: u32,
+) -> Option<u32> {
+    let (nx, nz) = (nx?, nz?);
+    let neighbor_lod = *desired_lods.get(&(nx, nz))?;
+
+    if neighbor_lod > own_lod {
+        Some(neighbor_lod)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,484 @@
+use bevy::asset::RenderAssetUsages;
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+use image::GrayImage;
+use std::collections::HashMap;
+use crate::world::map::terrain::border_generator::make_borders;
+use crate::world::map::terrain::terrain_normals::{unpack_normal, PackedHeightmapNormals};
+
+pub(crate) const VOID_HEIGHT: u8 = 0;
+pub(crate) const HEIGHT_SCALE: f32 = 0.3;
+const CELL_SIZE: f32 = 1.0;
+
+/// Per-edge LOD of the chunks bordering this one, one step coarser than
+/// `lod`. `None` means that side has no neighbor or isn't coarser, so this
+/// chunk's own edge vertices need no adjustment.
+#[derive(Default, Clone, Copy)]
+pub struct LodNeighbors {
+    pub left: Option<u32>,
+    pub right: Option<u32>,
+    pub top: Option<u32>,
+    pub bottom: Option<u32>,
+}
+
+struct ChunkBounds {
+    start_x: u32,
+    start_z: u32,
+    step: u32,
+    expanded_start_x: u32,
+    expanded_start_z: u32,
+    expanded_end_x: u32,
+    expanded_end_z: u32,
+    expanded_width: usize,
+    expanded_depth: usize,
+}
+
+pub fn generate_terrain_mesh(
+    start_x: u32,
+    start_z: u32,
+    heightmap: &GrayImage,
+    packed_normals: Option<&PackedHeightmapNormals>,
+    lod: u32,
+    neighbor_lods: LodNeighbors,
+) -> Option<Mesh> {
+    let chunk_size = 128;
+    let step = 1u32 << lod;
+
+    let bounds = calculate_chunk_bounds(start_x, start_z, chunk_size, step, heightmap);
+    if bounds.expanded_end_x <= bounds.expanded_start_x || bounds.expanded_end_z <= bounds.expanded_start_z {
+        return None;
+    }
+
+    if is_chunk_empty(start_x, start_z, chunk_size, heightmap) {
+        return None;
+    }
+
+    let (
+        mut positions,
+        mut normals,
+        mut uvs,
+        mut indices,
+        _vertex_map
+    ) = generate_mesh_data(bounds, heightmap, packed_normals, neighbor_lods);
+
+    if indices.is_empty() {
+        return None;
+    }
+
+    make_borders(start_x, start_z, chunk_size, step, heightmap, &mut positions, &mut normals, &mut uvs, &mut indices);
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default());
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.insert_indices(Indices::U32(indices));
+
+    Some(mesh)
+}
+
+/// Builds the positions and indices of a physics trimesh collider whose
+/// triangles are only a chunk's original-chunk ones (the ones
+/// `is_in_original_chunk` already gates `create_triangles` behind). The
+/// positions still include the expanded-border vertices, but no skirt
+/// triangle references them, so neighboring chunks' colliders don't overlap.
+pub fn generate_collision_mesh(
+    start_x: u32,
+    start_z: u32,
+    heightmap: &GrayImage,
+    lod: u32,
+) -> Option<(Vec<[f32; 3]>, Vec<u32>)> {
+    let chunk_size = 128;
+    let step = 1u32 << lod;
+
+    let bounds = calculate_chunk_bounds(start_x, start_z, chunk_size, step, heightmap);
+    if bounds.expanded_end_x <= bounds.expanded_start_x || bounds.expanded_end_z <= bounds.expanded_start_z {
+        return None;
+    }
+
+    if is_chunk_empty(start_x, start_z, chunk_size, heightmap) {
+        return None;
+    }
+
+    let (positions, _normals, _uvs, indices, _vertex_map) =
+        generate_mesh_data(bounds, heightmap, None, LodNeighbors::default());
+
+    if indices.is_empty() {
+        return None;
+    }
+
+    Some((positions, indices))
+}
+
+fn calculate_chunk_bounds(start_x: u32, start_z: u32, chunk_size: u32, step: u32, heightmap: &GrayImage) -> ChunkBounds {
+    let expanded_start_x = start_x.saturating_sub(step);
+    let expanded_start_z = start_z.saturating_sub(step);
+    let expanded_end_x = (start_x + chunk_size + step).min(heightmap.width());
+    let expanded_end_z = (start_z + chunk_size + step).min(heightmap.height());
+
+    ChunkBounds {
+        start_x,
+        start_z,
+        step,
+        expanded_start_x,
+        expanded_start_z,
+        expanded_end_x,
+        expanded_end_z,
+        expanded_width: (expanded_end_x - expanded_start_x) as usize,
+        expanded_depth: (expanded_end_z - expanded_start_z) as usize,
+    }
+}
+
+fn is_chunk_empty(start_x: u32, start_z: u32, chunk_size: u32, heightmap: &GrayImage) -> bool {
+    let end_x = start_x + chunk_size.min(heightmap.width() - start_x);
+    let end_z = start_z + chunk_size.min(heightmap.height() - start_z);
+
+    for z in start_z..end_z {
+        for x in start_x..end_x {
+            if heightmap.get_pixel(x, z)[0] != VOID_HEIGHT {
+                return false;
+            }
+        }
+    }
+
+    true
+}
+
+fn generate_mesh_data(
+    bounds: ChunkBounds,
+    heightmap: &GrayImage,
+    packed_normals: Option<&PackedHeightmapNormals>,
+    neighbor_lods: LodNeighbors,
+) -> (Vec<[f32; 3]>, Vec<[f32; 3]>, Vec<[f32; 2]>, Vec<u32>, HashMap<(u32, u32), u32>) {
+    let mut positions: Vec<[f32; 3]> = Vec::new();
+    let mut normals: Vec<[f32; 3]> = Vec::new();
+    let mut uvs: Vec<[f32; 2]> = Vec::new();
+    let mut indices: Vec<u32> = Vec::new();
+    let mut vertex_map: HashMap<(u32, u32), u32> = HashMap::new();
+
+    create_vertices(&bounds, heightmap, packed_normals, neighbor_lods, &mut positions, &mut normals, &mut uvs, &mut vertex_map);
+    let all_indices = create_triangles(&bounds, heightmap, &vertex_map, &mut indices);
+
+    if packed_normals.is_none() {
+        calculate_normals(&all_indices, &positions, &mut normals);
+    }
+
+    (positions, normals, uvs, indices, vertex_map)
+}
+
+fn create_vertices(
+    bounds: &ChunkBounds,
+    heightmap: &GrayImage,
+    packed_normals: Option<&PackedHeightmapNormals>,
+    neighbor_lods: LodNeighbors,
+    positions: &mut Vec<[f32; 3]>,
+    normals: &mut Vec<[f32; 3]>,
+    uvs: &mut Vec<[f32; 2]>,
+    vertex_map: &mut HashMap<(u32, u32), u32>
+) {
+    let step = bounds.step;
+    let chunk_size = 128;
+
+    let mut z = bounds.expanded_start_z;
+    while z <= bounds.expanded_end_z {
+        let mut x = bounds.expanded_start_x;
+        while x <= bounds.expanded_end_x {
+            if !is_vertex_needed(x, z, step, heightmap) {
+                x += step;
+                continue;
+            }
+
+            let (snapped_x, snapped_z, height_step) = snap_to_coarser_neighbor(
+                x, z, bounds.start_x, bounds.start_z, chunk_size, step, neighbor_lods,
+            );
+            let snapped_x = snapped_x.clamp(bounds.expanded_start_x, bounds.expanded_end_x);
+            let snapped_z = snapped_z.clamp(bounds.expanded_start_z, bounds.expanded_end_z);
+
+            let vertex_index = if let Some(&existing) = vertex_map.get(&(snapped_x, snapped_z)) {
+                existing
+            } else {
+                let height = calculate_vertex_height(snapped_x, snapped_z, height_step, heightmap);
+                let local_x = (snapped_x as i32 - bounds.start_x as i32) as f32;
+                let local_z = (snapped_z as i32 - bounds.start_z as i32) as f32;
+                let vertex_index = positions.len() as u32;
+                positions.push([local_x, height, local_z]);
+                normals.push(sample_normal(snapped_x, snapped_z, heightmap, packed_normals));
+                uvs.push([
+                    (snapped_x - bounds.expanded_start_x) as f32 / bounds.expanded_width as f32,
+                    (snapped_z - bounds.expanded_start_z) as f32 / bounds.expanded_depth as f32
+                ]);
+                vertex_map.insert((snapped_x, snapped_z), vertex_index);
+                vertex_index
+            };
+
+            vertex_map.insert((x, z), vertex_index);
+
+            x += step;
+        }
+        z += step;
+    }
+}
+
+/// When the neighbor across a chunk edge is a coarser LOD, it only has
+/// vertices on its own coarse grid. Snapping this chunk's edge vertices down
+/// onto that grid (instead of its own finer one) keeps the two skirts
+/// sharing exact positions so they don't crack apart. The left/right edges
+/// run along `z`, so a coarser left/right neighbor snaps `z`; the top/bottom
+/// edges run along `x`, so a coarser top/bottom neighbor snaps `x`. Also
+/// returns the step to compute the snapped vertex's height at, so the seam
+/// matches the neighbor's height for that position, not just its XZ — the
+/// coarser of this chunk's own step and any applied neighbor step.
+fn snap_to_coarser_neighbor(
+    x: u32, z: u32,
+    start_x: u32, start_z: u32,
+    chunk_size: u32,
+    step: u32,
+    neighbor_lods: LodNeighbors,
+) -> (u32, u32, u32) {
+    let mut snapped_x = x;
+    let mut snapped_z = z;
+    let mut height_step = step;
+
+    if x <= start_x {
+        if let Some(lod) = neighbor_lods.left {
+            snapped_z = snap_coord(z, start_z, lod);
+            height_step = height_step.max(1u32 << lod);
+        }
+    } else if x >= start_x + chunk_size {
+        if let Some(lod) = neighbor_lods.right {
+            snapped_z = snap_coord(z, start_z, lod);
+            height_step = height_step.max(1u32 << lod);
+        }
+    }
+
+    if z <= start_z {
+        if let Some(lod) = neighbor_lods.top {
+            snapped_x = snap_coord(x, start_x, lod);
+            height_step = height_step.max(1u32 << lod);
+        }
+    } else if z >= start_z + chunk_size {
+        if let Some(lod) = neighbor_lods.bottom {
+            snapped_x = snap_coord(x, start_x, lod);
+            height_step = height_step.max(1u32 << lod);
+        }
+    }
+
+    (snapped_x, snapped_z, height_step)
+}
+
+fn snap_coord(value: u32, origin: u32, neighbor_lod: u32) -> u32 {
+    let neighbor_step = 1u32 << neighbor_lod;
+    let offset = value as i32 - origin as i32;
+    let snapped_offset = (offset as f32 / neighbor_step as f32).round() as i32 * neighbor_step as i32;
+    (origin as i32 + snapped_offset).max(0) as u32
+}
+
+/// Looks up the GPU-computed normal for the heightmap texel under this vertex.
+/// When the compute pass hasn't produced data yet (or the heightmap doesn't
+/// match what was baked), falls back to a flat up normal. `generate_mesh_data`
+/// replaces these with accumulated per-triangle normals from
+/// `calculate_normals` only when no packed normals were passed at all; an
+/// empty or mismatched set leaves the flat normal in the mesh.
+fn sample_normal(
+    x: u32, z: u32,
+    heightmap: &GrayImage,
+    packed_normals: Option<&PackedHeightmapNormals>
+) -> [f32; 3] {
+    let Some(packed_normals) = packed_normals else {
+        return [0.0, 1.0, 0.0];
+    };
+
+    if packed_normals.width != heightmap.width() || x >= heightmap.width() || z >= heightmap.height() {
+        return [0.0, 1.0, 0.0];
+    }
+
+    let index = (z * packed_normals.width + x) as usize;
+    match packed_normals.data.get(index) {
+        Some(&packed) => unpack_normal(packed, CELL_SIZE),
+        None => [0.0, 1.0, 0.0],
+    }
+}
+
+fn is_vertex_needed(
+    x: u32, z: u32,
+    step: u32,
+    heightmap: &GrayImage
+) -> bool {
+    block_is_solid(x.saturating_sub(step), z.saturating_sub(step), step, heightmap)
+        || block_is_solid(x, z.saturating_sub(step), step, heightmap)
+        || block_is_solid(x.saturating_sub(step), z, step, heightmap)
+        || block_is_solid(x, z, step, heightmap)
+}
+
+/// A coarse `step`x`step` block is solid only if every pixel inside it is
+/// non-void; a single void pixel demotes the whole cell to empty so
+/// coastlines don't bleed into the LOD'd terrain.
+pub(crate) fn block_is_solid(block_x: u32, block_z: u32, step: u32, heightmap: &GrayImage) -> bool {
+    average_block_height(block_x, block_z, step, heightmap).is_some()
+}
+
+fn average_block_height(block_x: u32, block_z: u32, step: u32, heightmap: &GrayImage) -> Option<f32> {
+    let mut sum = 0.0;
+    let mut count = 0u32;
+
+    for dz in 0..step {
+        for dx in 0..step {
+            let px = block_x + dx;
+            let pz = block_z + dz;
+
+            if px >= heightmap.width() || pz >= heightmap.height() {
+                continue;
+            }
+
+            let pixel = heightmap.get_pixel(px, pz)[0];
+            if pixel == VOID_HEIGHT {
+                return None;
+            }
+
+            sum += pixel as f32;
+            count += 1;
+        }
+    }
+
+    if count > 0 {
+        Some(sum / count as f32)
+    } else {
+        None
+    }
+}
+
+pub(crate) fn calculate_vertex_height(
+    x: u32, z: u32,
+    step: u32,
+    heightmap: &GrayImage
+) -> f32 {
+    let mut height_sum = 0.0;
+    let mut count = 0;
+
+    let adjacent_blocks = [
+        (x.saturating_sub(step), z.saturating_sub(step)),
+        (x, z.saturating_sub(step)),
+        (x.saturating_sub(step), z),
+        (x, z)
+    ];
+
+    for (bx, bz) in adjacent_blocks {
+        if let Some(avg) = average_block_height(bx, bz, step, heightmap) {
+            height_sum += avg;
+            count += 1;
+        }
+    }
+
+    if count > 0 {
+        (height_sum / count as f32) * HEIGHT_SCALE
+    } else {
+        0.0
+    }
+}
+
+fn create_triangles(
+    bounds: &ChunkBounds,
+    heightmap: &GrayImage,
+    vertex_map: &HashMap<(u32, u32), u32>,
+    indices: &mut Vec<u32>
+) -> Vec<u32> {
+    let mut all_indices: Vec<u32> = Vec::new();
+    let step = bounds.step;
+
+    let mut z = bounds.expanded_start_z;
+    while z < bounds.expanded_end_z {
+        let mut x = bounds.expanded_start_x;
+        while x < bounds.expanded_end_x {
+            if !block_is_solid(x, z, step, heightmap) {
+                x += step;
+                continue;
+            }
+
+            if let (
+                Some(&top_left),
+                Some(&top_right),
+                Some(&bottom_right),
+                Some(&bottom_left)
+            ) = (
+                vertex_map.get(&(x, z)),
+                vertex_map.get(&(x + step, z)),
+                vertex_map.get(&(x + step, z + step)),
+                vertex_map.get(&(x, z + step))
+            ) {
+                all_indices.push(top_right);
+                all_indices.push(top_left);
+                all_indices.push(bottom_right);
+
+                all_indices.push(top_left);
+                all_indices.push(bottom_left);
+                all_indices.push(bottom_right);
+
+                if is_in_original_chunk(x, z, bounds.start_x, bounds.start_z) {
+                    indices.push(top_right);
+                    indices.push(top_left);
+                    indices.push(bottom_right);
+
+                    indices.push(top_left);
+                    indices.push(bottom_left);
+                    indices.push(bottom_right);
+                }
+            }
+
+            x += step;
+        }
+        z += step;
+    }
+
+    all_indices
+}
+
+fn is_in_original_chunk(x: u32, z: u32, start_x: u32, start_z: u32) -> bool {
+    let chunk_size = 128;
+    x >= start_x && x < start_x + chunk_size && z >= start_z && z < start_z + chunk_size
+}
+
+fn calculate_normals(
+    all_indices: &[u32],
+    positions: &[[f32; 3]],
+    normals: &mut [[f32; 3]]
+) {
+    for normal in normals.iter_mut() {
+        *normal = [0.0, 0.0, 0.0];
+    }
+
+    for i in (0..all_indices.len()).step_by(3) {
+        if i + 2 >= all_indices.len() {
+            continue;
+        }
+
+        let i0 = all_indices[i] as usize;
+        let i1 = all_indices[i + 1] as usize;
+        let i2 = all_indices[i + 2] as usize;
+        let p0 = positions[i0];
+        let p1 = positions[i1];
+        let p2 = positions[i2];
+        let v1 = [p1[0] - p0[0], p1[1] - p0[1], p1[2] - p0[2]];
+        let v2 = [p2[0] - p0[0], p2[1] - p0[1], p2[2] - p0[2]];
+        let normal = [
+            v1[1] * v2[2] - v1[2] * v2[1],
+            v1[2] * v2[0] - v1[0] * v2[2],
+            v1[0] * v2[1] - v1[1] * v2[0]
+        ];
+        for &idx in &[i0, i1, i2] {
+            normals[idx][0] += normal[0];
+            normals[idx][1] += normal[1];
+            normals[idx][2] += normal[2];
+        }
+    }
+
+    for normal in normals.iter_mut() {
+        let length = (normal[0] * normal[0] + normal[1] * normal[1] + normal[2] * normal[2]).sqrt();
+
+        if length > 0.0001 {
+            normal[0] /= length;
+            normal[1] /= length;
+            normal[2] /= length;
+        } else {
+            *normal = [0.0, 1.0, 0.0];
+        }
+    }
+}
@@ -0,0 +1,229 @@
+use bevy::prelude::*;
+use bevy::render::extract_resource::{ExtractResource, ExtractResourcePlugin};
+use bevy::render::render_asset::RenderAssets;
+use bevy::render::render_resource::{
+    BindGroupEntries, BindGroupLayout, BindGroupLayoutEntries, BindingType, Buffer,
+    BufferBindingType, BufferDescriptor, BufferUsages, CachedComputePipelineId, CachedPipelineState,
+    ComputePassDescriptor, ComputePipelineDescriptor, Maintain, MapMode, PipelineCache,
+    ShaderStages, TextureSampleType,
+};
+use bevy::render::renderer::{RenderContext, RenderDevice, RenderQueue};
+use bevy::render::texture::GpuImage;
+use bevy::render::{Render, RenderApp, RenderSet};
+use crossbeam_channel::{Receiver, Sender};
+use crate::world::map::terrain::terrain_generator::HEIGHT_SCALE;
+
+const NORMAL_SHADER_PATH: &str = "shaders/heightmap_normals.wgsl";
+pub const MAX_DIFF: f32 = 64.0;
+
+/// Packed per-texel slope data produced by the GPU compute pass, ready for
+/// `unpack_normal` to turn into a vertex normal without any CPU-side
+/// triangle accumulation.
+#[derive(Resource, Default, Clone)]
+pub struct PackedHeightmapNormals {
+    pub width: u32,
+    pub data: Vec<u32>,
+}
+
+#[derive(Resource, Clone, ExtractResource)]
+pub struct HeightmapNormalsSource {
+    pub texture: Handle<Image>,
+    pub width: u32,
+    pub height: u32,
+}
+
+pub struct TerrainNormalsPlugin;
+
+impl Plugin for TerrainNormalsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(ExtractResourcePlugin::<HeightmapNormalsSource>::default());
+        app.init_resource::<PackedHeightmapNormals>();
+
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        app.insert_resource(NormalsReadback { receiver });
+        app.add_systems(Update, receive_packed_normals);
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app
+            .insert_resource(NormalsReadbackSender { sender })
+            .init_resource::<TerrainNormalsState>()
+            .add_systems(Render, (prepare_pipeline, dispatch_normals_pass).chain().in_set(RenderSet::Cleanup));
+    }
+
+    fn finish(&self, app: &mut App) {
+        if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app.init_resource::<TerrainNormalsPipeline>();
+        }
+    }
+}
+
+#[derive(Resource)]
+struct NormalsReadbackSender {
+    sender: Sender<PackedHeightmapNormals>,
+}
+
+#[derive(Resource)]
+struct NormalsReadback {
+    receiver: Receiver<PackedHeightmapNormals>,
+}
+
+#[derive(Resource, Default)]
+struct TerrainNormalsState {
+    dispatched: bool,
+}
+
+#[derive(Resource)]
+struct TerrainNormalsPipeline {
+    bind_group_layout: BindGroupLayout,
+    pipeline_id: CachedComputePipelineId,
+}
+
+impl FromWorld for TerrainNormalsPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+        let bind_group_layout = render_device.create_bind_group_layout(
+            "terrain_normals_bind_group_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::COMPUTE,
+                (
+                    bevy::render::render_resource::binding_types::texture_2d(TextureSampleType::Float { filterable: false }),
+                    bevy::render::render_resource::binding_types::storage_buffer::<u32>(false),
+                ),
+            ),
+        );
+
+        let shader = world.resource::<AssetServer>().load(NORMAL_SHADER_PATH);
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let pipeline_id = pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
+            label: Some("terrain_normals_pipeline".into()),
+            layout: vec![bind_group_layout.clone()],
+            push_constant_ranges: Vec::new(),
+            shader,
+            shader_defs: Vec::new(),
+            entry_point: "main".into(),
+            zero_initialize_workgroup_memory: false,
+        });
+
+        Self {
+            bind_group_layout,
+            pipeline_id,
+        }
+    }
+}
+
+fn prepare_pipeline(pipeline: Option<Res<TerrainNormalsPipeline>>, pipeline_cache: Res<PipelineCache>) {
+    let Some(pipeline) = pipeline else { return };
+    if let CachedPipelineState::Err(err) = pipeline_cache.get_compute_pipeline_state(pipeline.pipeline_id) {
+        error!("terrain normals pipeline failed to compile: {err}");
+    }
+}
+
+fn dispatch_normals_pass(
+    source: Option<Res<HeightmapNormalsSource>>,
+    pipeline: Option<Res<TerrainNormalsPipeline>>,
+    pipeline_cache: Res<PipelineCache>,
+    gpu_images: Res<RenderAssets<GpuImage>>,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    sender: Res<NormalsReadbackSender>,
+    mut state: ResMut<TerrainNormalsState>,
+) {
+    let (Some(source), Some(pipeline)) = (source, pipeline) else {
+        return;
+    };
+
+    if state.dispatched {
+        return;
+    }
+
+    let Some(gpu_image) = gpu_images.get(&source.texture) else {
+        return;
+    };
+
+    let Some(compute_pipeline) = pipeline_cache.get_compute_pipeline(pipeline.pipeline_id) else {
+        return;
+    };
+
+    let texel_count = (source.width * source.height) as u64;
+    let buffer_size = texel_count * std::mem::size_of::<u32>() as u64;
+
+    let storage_buffer = render_device.create_buffer(&BufferDescriptor {
+        label: Some("terrain_normals_storage_buffer"),
+        size: buffer_size,
+        usage: BufferUsages::STORAGE | BufferUsages::COPY_SRC,
+        mapped_at_creation: false,
+    });
+
+    let staging_buffer = render_device.create_buffer(&BufferDescriptor {
+        label: Some("terrain_normals_staging_buffer"),
+        size: buffer_size,
+        usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+
+    let bind_group = render_device.create_bind_group(
+        Some("terrain_normals_bind_group"),
+        &pipeline.bind_group_layout,
+        &BindGroupEntries::sequential((&gpu_image.texture_view, storage_buffer.as_entire_buffer_binding())),
+    );
+
+    let mut encoder = render_device.create_command_encoder(&Default::default());
+    {
+        let mut pass = encoder.begin_compute_pass(&ComputePassDescriptor::default());
+        pass.set_pipeline(compute_pipeline);
+        pass.set_bind_group(0, &bind_group, &[]);
+        pass.dispatch_workgroups((source.width + 7) / 8, (source.height + 7) / 8, 1);
+    }
+    encoder.copy_buffer_to_buffer(&storage_buffer, 0, &staging_buffer, 0, buffer_size);
+    render_queue.submit([encoder.finish()]);
+
+    state.dispatched = true;
+    read_back_buffer(staging_buffer, source.width, &render_device, &sender.sender);
+}
+
+fn read_back_buffer(buffer: Buffer, width: u32, render_device: &RenderDevice, sender: &Sender<PackedHeightmapNormals>) {
+    let slice = buffer.slice(..);
+    let (tx, rx) = crossbeam_channel::bounded(1);
+    slice.map_async(MapMode::Read, move |result| {
+        let _ = tx.send(result);
+    });
+    render_device.poll(Maintain::Wait);
+
+    if let Ok(Ok(())) = rx.recv() {
+        let data = slice.get_mapped_range();
+        let packed: Vec<u32> = bytemuck::cast_slice(&data).to_vec();
+        drop(data);
+        buffer.unmap();
+        let _ = sender.send(PackedHeightmapNormals { width, data: packed });
+    }
+}
+
+fn receive_packed_normals(readback: Res<NormalsReadback>, mut packed: ResMut<PackedHeightmapNormals>) {
+    while let Ok(normals) = readback.receiver.try_recv() {
+        *packed = normals;
+    }
+}
+
+/// Reconstructs the normalized surface normal for a heightmap texel from the
+/// GPU-packed slope byte pair, mirroring the packing done in
+/// `heightmap_normals.wgsl`. `dx`/`dz` are raw (unscaled) heightmap slope, but
+/// the mesh renders height scaled by `HEIGHT_SCALE`, so the rise term is
+/// widened by `1 / HEIGHT_SCALE` to describe the same, flatter surface that's
+/// actually drawn.
+pub fn unpack_normal(packed: u32, cell_size: f32) -> [f32; 3] {
+    let px = (packed >> 8) & 0xFF;
+    let pz = packed & 0xFF;
+
+    let dx = (px as f32 - 128.0) / 127.0 * MAX_DIFF;
+    let dz = (pz as f32 - 128.0) / 127.0 * MAX_DIFF;
+
+    let normal = Vec3::new(-dx, 2.0 * cell_size / HEIGHT_SCALE, -dz).normalize_or_zero();
+    if normal == Vec3::ZERO {
+        [0.0, 1.0, 0.0]
+    } else {
+        normal.into()
+    }
+}